@@ -7,7 +7,7 @@ use time::OffsetDateTime;
 use tokio::try_join;
 use tracing::{error, info, instrument};
 
-use super::helpers::ticket_auth;
+use super::{helpers::ticket_auth, notifications};
 use crate::{
     models::{
         extra_song_info::ExtraSongInfo,
@@ -64,6 +64,14 @@ pub async fn fetch_song_id(
 
     let steam_player = ticket_auth(&payload.ticket, &state.steam_api).await?;
 
+    #[cfg(feature = "stats")]
+    {
+        let mut redis = state.redis.clone();
+        if let Err(e) = crate::stats::record_active_player(&mut redis, steam_player).await {
+            error!("Failed to record active player in stats: {e}");
+        }
+    }
+
     let mut conn = state.db.get().await?;
     let parsed_modifiers = parse_from_title(&payload.song);
 
@@ -98,16 +106,36 @@ pub async fn fetch_song_id(
                 payload.artist, payload.song, steam_player, payload.league, payload.mbid, payload.release_mbid
             );
 
-            let song = NewSong::new(
+            let (song, _created) = NewSong::new(
                 &remove_from_title(&payload.song),
                 &payload.artist,
                 parsed_modifiers,
             )
-            .find_or_create(&mut conn)
+            .find_or_create(&mut conn, state.song_match_threshold)
             .await?;
 
-            song.add_metadata_mbid(recording_mbid, payload.release_mbid.as_deref(), &mut conn)
-                .await?;
+            #[cfg(feature = "metrics")]
+            if _created {
+                state.metrics.songs_created.with_label_values(&[]).inc();
+            }
+
+            let mbid_result = song
+                .add_metadata_mbid(
+                    recording_mbid,
+                    payload.release_mbid.as_deref(),
+                    &state.musicbrainz,
+                    &mut conn,
+                )
+                .await;
+
+            #[cfg(feature = "metrics")]
+            state
+                .metrics
+                .musicbrainz_lookups
+                .with_label_values(&[if mbid_result.is_ok() { "success" } else { "failure" }])
+                .inc();
+
+            mbid_result?;
 
             Ok(Xml(SongIdResponse {
                 status: "allgood".to_owned(),
@@ -115,14 +143,19 @@ pub async fn fetch_song_id(
             }))
         }
     } else {
-        let song = NewSong::new(
+        let (song, _created) = NewSong::new(
             &remove_from_title(&payload.song),
             &payload.artist,
             parsed_modifiers,
         )
-        .find_or_create(&mut conn)
+        .find_or_create(&mut conn, state.song_match_threshold)
         .await?;
 
+        #[cfg(feature = "metrics")]
+        if _created {
+            state.metrics.songs_created.with_label_values(&[]).inc();
+        }
+
         info!(
             "Song {} - {} looked up by {} (Steam), league {:?}, MBID {:?}, release MBID {:?}",
             song.artist,
@@ -140,6 +173,166 @@ pub async fn fetch_song_id(
     }
 }
 
+#[derive(Deserialize)]
+pub struct BatchSongIdEntry {
+    artist: String,
+    song: String,
+    league: League,
+    mbid: Option<String>,
+    #[serde(rename = "releasembid")]
+    release_mbid: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "BATCH")]
+pub struct BatchSongIdRequest {
+    ticket: String,
+    #[serde(rename = "song")]
+    songs: Vec<BatchSongIdEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename = "RESULT")]
+pub struct BatchSongIdResponse {
+    #[serde(rename = "@status")]
+    status: String,
+    /// One `<songid>` element per resolved song, in request order — a flat list of IDs rather
+    /// than wrapping each one in its own struct, so a single entry serializes to `<songid>5</songid>`
+    /// the same way `SongIdResponse::song_id` does, not `<songid><songid>5</songid></songid>`.
+    #[serde(rename = "songid")]
+    song_ids: Vec<i32>,
+}
+
+/// Resolves or creates song IDs for a batch of (artist, song, league, optional MBID) entries in
+/// a single request.
+///
+/// Authenticates the Steam ticket once and resolves/creates all songs in a single DB
+/// transaction, instead of clients re-running `fetch_song_id` (and `ticket_auth`) once per song
+/// when importing or pre-caching a playlist. MusicBrainz metadata is fetched afterwards, once
+/// the transaction has committed, the same way the single-song `fetch_song_id` does — each
+/// lookup goes through the shared rate limiter, and holding the DB transaction open across up to
+/// one MusicBrainz round trip per song would serialize the whole batch behind it.
+///
+/// # Errors
+///
+/// This fails if:
+/// - The response fails to serialize
+/// - Authenticating with Steam fails
+/// - Any song fails to be created/retrieved
+#[instrument(skip_all)]
+pub async fn fetch_song_ids_batch(
+    State(state): State<AppState>,
+    Xml(payload): Xml<BatchSongIdRequest>,
+) -> Result<Xml<BatchSongIdResponse>, RouteError> {
+    use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection};
+
+    use crate::{
+        schema::{extra_song_info::dsl::*, songs::dsl::*},
+        util::modifiers::{parse_from_title, remove_from_title},
+    };
+
+    let steam_player = ticket_auth(&payload.ticket, &state.steam_api).await?;
+
+    #[cfg(feature = "stats")]
+    {
+        let mut redis = state.redis.clone();
+        if let Err(e) = crate::stats::record_active_player(&mut redis, steam_player).await {
+            error!("Failed to record active player in stats: {e}");
+        }
+    }
+
+    info!(
+        "Batch song ID lookup of {} songs by {} (Steam)",
+        payload.songs.len(),
+        steam_player
+    );
+
+    let mut conn = state.db.get().await?;
+
+    let (song_ids, pending_metadata) = conn
+        .transaction::<_, RouteError, _>(|conn| {
+            async move {
+                let mut song_ids = Vec::with_capacity(payload.songs.len());
+                let mut pending_metadata = Vec::new();
+
+                for entry in &payload.songs {
+                    let parsed_modifiers = parse_from_title(&entry.song);
+
+                    let song = if let Some(recording_mbid) = &entry.mbid {
+                        let existing_by_mbid = songs
+                            .inner_join(extra_song_info)
+                            .filter(
+                                mbid.eq(recording_mbid)
+                                    .and(modifiers.is_not_distinct_from(&parsed_modifiers)),
+                            )
+                            .first::<(Song, ExtraSongInfo)>(conn)
+                            .await
+                            .optional()?
+                            .map(|(song, _)| song);
+
+                        if let Some(song) = existing_by_mbid {
+                            song
+                        } else {
+                            let (song, _created) = NewSong::new(
+                                &remove_from_title(&entry.song),
+                                &entry.artist,
+                                parsed_modifiers,
+                            )
+                            .find_or_create(conn, state.song_match_threshold)
+                            .await?;
+
+                            pending_metadata.push((
+                                song.clone(),
+                                recording_mbid.clone(),
+                                entry.release_mbid.clone(),
+                            ));
+
+                            song
+                        }
+                    } else {
+                        let (song, _created) = NewSong::new(
+                            &remove_from_title(&entry.song),
+                            &entry.artist,
+                            parsed_modifiers,
+                        )
+                        .find_or_create(conn, state.song_match_threshold)
+                        .await?;
+
+                        song
+                    };
+
+                    info!(
+                        "Song {} - {} looked up by {} (Steam), league {:?} (batch)",
+                        song.artist, song.title, steam_player, entry.league
+                    );
+
+                    song_ids.push(song.id);
+                }
+
+                Ok((song_ids, pending_metadata))
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    // Fetch MusicBrainz metadata for newly-created songs outside the transaction above, so a
+    // rate-limited or slow lookup never holds a Postgres transaction open.
+    for (song, recording_mbid, release_mbid) in pending_metadata {
+        song.add_metadata_mbid(
+            &recording_mbid,
+            release_mbid.as_deref(),
+            &state.musicbrainz,
+            &mut conn,
+        )
+        .await?;
+    }
+
+    Ok(Xml(BatchSongIdResponse {
+        status: "allgood".to_owned(),
+        song_ids,
+    }))
+}
+
 #[derive(Deserialize)]
 pub struct SendRideRequest {
     ticket: String,
@@ -209,6 +402,14 @@ pub async fn send_ride(
 
     let steam_player = ticket_auth(&payload.ticket, &state.steam_api).await?;
 
+    #[cfg(feature = "stats")]
+    {
+        let mut redis = state.redis.clone();
+        if let Err(e) = crate::stats::record_active_player(&mut redis, steam_player).await {
+            error!("Failed to record active player in stats: {e}");
+        }
+    }
+
     info!(
         "Score received on {} from {} (Steam) with score {}, using {:?}. MBID {:?}, release MBID {:?}",
         &payload.song_id, &steam_player, &payload.score, &payload.vehicle, &payload.mbid, &payload.release_mbid
@@ -237,9 +438,12 @@ pub async fn send_ride(
         .optional()?;
 
     // construct part of the response that's for dethroning
+    let mut dethrone_event = None;
     let beat_score = if let Some(current_top) = current_top {
+        let dethroned = current_top.0.score < payload.score;
+
         // Check if the player dethroned the current top score
-        if current_top.0.score < payload.score {
+        if dethroned {
             info!(
                 "Player {} (Steam) dethroned {} on {} with score {}",
                 steam_player, current_top.1.id, current_top.0.song_id, payload.score
@@ -261,8 +465,21 @@ pub async fn send_ride(
             false
         };
 
+        if dethroned {
+            dethrone_event = Some(notifications::DethroneEvent {
+                song_id: song.id,
+                dethroned_player_id: current_top.1.id,
+                dethroned_player_name: current_top.1.username.clone(),
+                new_player_name: player.username.clone(),
+                old_score: current_top.0.score,
+                new_score: payload.score,
+                reign_seconds: reign_duration.whole_seconds(),
+                is_friend: mutual,
+            });
+        }
+
         BeatScore {
-            dethroned: current_top.0.score < payload.score,
+            dethroned,
             friend: mutual,
             rival_name: current_top.1.username,
             rival_score: current_top.0.score,
@@ -306,16 +523,60 @@ pub async fn send_ride(
     .create_or_update(&mut conn, &state.redis)
     .await?;
 
+    #[cfg(feature = "stats")]
+    {
+        let mut redis = state.redis.clone();
+        if let Err(e) = crate::stats::record_score_submitted(&mut redis, song.id).await {
+            error!("Failed to record score submission in stats: {e}");
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    {
+        let league_label = format!("{:?}", payload.league);
+        state
+            .metrics
+            .scores_submitted
+            .with_label_values(&[&league_label])
+            .inc();
+        if beat_score.dethroned {
+            state
+                .metrics
+                .dethrones
+                .with_label_values(&[&league_label])
+                .inc();
+        }
+    }
+
     // Add MusicBrainz metadata, if no extra metadata exists already
     // we're doing this here because we need the song length to search for the recording
-    if let Err(e) = song
-        .auto_add_metadata(payload.song_length * 10, &mut conn)
-        .await
-    {
+    let metadata_result = song
+        .auto_add_metadata(payload.song_length * 10, &state.musicbrainz, &mut conn)
+        .await;
+
+    #[cfg(feature = "metrics")]
+    state
+        .metrics
+        .musicbrainz_lookups
+        .with_label_values(&[if metadata_result.is_ok() {
+            "success"
+        } else {
+            "failure"
+        }])
+        .inc();
+
+    if let Err(e) = metadata_result {
         error!("Failed to add metadata for song {}: {}", song.id, e);
     }
 
-    // TODO: Implement dethrone notifications
+    // Publish the dethrone event for the notification consumer task to pick up. Sending is
+    // synchronous and non-blocking; delivery to the actual dispatcher(s) happens entirely off
+    // this request's critical path. An `Err` here just means no consumer is currently
+    // subscribed (e.g. no dispatcher configured), which is fine to ignore.
+    if let Some(event) = dethrone_event {
+        let _ = state.dethrone_events.send(event);
+    }
+
     Ok(Xml(SendRideResponse {
         status: "allgood".to_owned(),
         song_id: new_score.song_id,
@@ -416,6 +677,14 @@ pub async fn get_rides(
         steam_player, payload.song_id
     );
 
+    #[cfg(feature = "stats")]
+    {
+        let mut redis = state.redis.clone();
+        if let Err(e) = crate::stats::record_active_player(&mut redis, steam_player).await {
+            error!("Failed to record active player in stats: {e}");
+        }
+    }
+
     let mut conn = state.db.get().await?;
 
     let player: Player = Player::find_by_steam_id(steam_player)
@@ -444,9 +713,19 @@ pub async fn get_rides(
         let nearby_future =
             Score::game_get_nearby(payload.song_id, league, player.location_id, &mut conn2);
 
+        #[cfg(feature = "metrics")]
+        let query_started_at = std::time::Instant::now();
+
         let (global_scores, rival_scores, nearby_scores) =
             try_join!(global_future, rival_future, nearby_future)?;
 
+        #[cfg(feature = "metrics")]
+        state
+            .metrics
+            .db_query_duration
+            .with_label_values(&["get_rides"])
+            .observe(query_started_at.elapsed().as_secs_f64());
+
         global_rides.push(create_league_rides(league, global_scores));
         rival_rides.push(create_league_rides(league, rival_scores));
         nearby_rides.push(create_league_rides(league, nearby_scores));
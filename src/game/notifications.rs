@@ -0,0 +1,106 @@
+//! Dethrone notification pipeline.
+//!
+//! `send_ride` enqueues a `DethroneEvent` onto `AppState::dethrone_events`, a broadcast channel,
+//! whenever a player's top score on a song is beaten. A dedicated consumer task (spawned via
+//! `spawn_dethrone_consumer`) owns the receiving end and hands each event to every configured
+//! `DethroneDispatcher`, so publishing an event never adds latency to score submission and
+//! additional dispatchers can be wired in without touching `send_ride`.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+/// Capacity of the dethrone broadcast channel. A lagging consumer drops the oldest events past
+/// this many unconsumed; dethrones are informational, so a dropped notification under a burst is
+/// preferable to unbounded buffering.
+pub const DETHRONE_CHANNEL_CAPACITY: usize = 256;
+
+/// Bounds how long a single webhook POST can hang, so a stalled endpoint can't block the
+/// dispatch of later events.
+const DISPATCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A dethrone event, as handed to a `DethroneDispatcher`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DethroneEvent {
+    pub song_id: i32,
+    pub dethroned_player_id: i32,
+    pub dethroned_player_name: String,
+    pub new_player_name: String,
+    pub old_score: i32,
+    pub new_score: i32,
+    pub reign_seconds: i64,
+    /// `true` when the dethroned player and the new top scorer have a mutual rivalry, i.e. this
+    /// is a grudge match rather than an anonymous dethrone.
+    pub is_friend: bool,
+}
+
+/// Delivers dethrone events to some external sink (Discord, Matrix, ...).
+#[async_trait]
+pub trait DethroneDispatcher: Send + Sync {
+    async fn dispatch(&self, event: &DethroneEvent);
+}
+
+/// Dispatcher that POSTs the event as JSON to a configured webhook URL (Discord/Matrix-style
+/// incoming webhooks).
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl WebhookDispatcher {
+    #[must_use]
+    pub fn new(webhook_url: String) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(DISPATCH_TIMEOUT)
+                .build()
+                .expect("failed to build webhook HTTP client"),
+            webhook_url,
+        }
+    }
+}
+
+#[async_trait]
+impl DethroneDispatcher for WebhookDispatcher {
+    async fn dispatch(&self, event: &DethroneEvent) {
+        if let Err(e) = self.client.post(&self.webhook_url).json(event).send().await {
+            error!("Failed to deliver dethrone webhook: {e}");
+        }
+    }
+}
+
+/// Spawns the consumer task that drains `receiver` and hands each event to every dispatcher in
+/// `dispatchers`. Intended to be called once at startup with the receiving end of
+/// `AppState::dethrone_events`.
+///
+/// Each dispatch runs in its own spawned task rather than being awaited in the consumer loop, so
+/// one dispatcher stalling (a webhook endpoint that hangs instead of erroring, say) can't back up
+/// delivery to every other dispatcher or delay draining later events off the channel.
+pub fn spawn_dethrone_consumer(
+    mut receiver: broadcast::Receiver<DethroneEvent>,
+    dispatchers: Vec<Arc<dyn DethroneDispatcher>>,
+) {
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let event = Arc::new(event);
+                    for dispatcher in &dispatchers {
+                        let dispatcher = dispatcher.clone();
+                        let event = event.clone();
+                        tokio::spawn(async move {
+                            dispatcher.dispatch(&event).await;
+                        });
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Dethrone notification consumer lagged, dropped {skipped} events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
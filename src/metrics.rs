@@ -0,0 +1,122 @@
+//! Prometheus Pushgateway metrics, enabled via the `metrics` cargo feature.
+//!
+//! There's no long-lived scrape endpoint tied to per-request handlers here, so instead of
+//! exposing `/metrics` we gather the registry on a background interval and push it to an
+//! operator-configured Pushgateway.
+
+use std::time::Duration;
+
+use prometheus::{HistogramVec, IntCounterVec, Registry};
+use tracing::{error, info};
+
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    pub scores_submitted: IntCounterVec,
+    pub dethrones: IntCounterVec,
+    pub songs_created: IntCounterVec,
+    pub musicbrainz_lookups: IntCounterVec,
+    pub db_query_duration: HistogramVec,
+}
+
+pub struct MetricsConfig {
+    pub pushgateway_url: String,
+    pub job_name: String,
+    pub push_interval: Duration,
+}
+
+impl Metrics {
+    /// # Errors
+    ///
+    /// This fails if any of the metrics fail to register with the internal registry.
+    pub fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new();
+
+        let scores_submitted = IntCounterVec::new(
+            prometheus::Opts::new(
+                "wavebreaker_scores_submitted_total",
+                "Scores submitted via send_ride",
+            ),
+            &["league"],
+        )?;
+        let dethrones = IntCounterVec::new(
+            prometheus::Opts::new("wavebreaker_dethrones_total", "Top scores dethroned"),
+            &["league"],
+        )?;
+        let songs_created = IntCounterVec::new(
+            prometheus::Opts::new(
+                "wavebreaker_songs_created_total",
+                "New songs created by fetch_song_id",
+            ),
+            &[],
+        )?;
+        let musicbrainz_lookups = IntCounterVec::new(
+            prometheus::Opts::new(
+                "wavebreaker_musicbrainz_lookups_total",
+                "MusicBrainz metadata fetch attempts",
+            ),
+            &["result"],
+        )?;
+        let db_query_duration = HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "wavebreaker_db_query_duration_seconds",
+                "DB query latency",
+            ),
+            &["query"],
+        )?;
+
+        registry.register(Box::new(scores_submitted.clone()))?;
+        registry.register(Box::new(dethrones.clone()))?;
+        registry.register(Box::new(songs_created.clone()))?;
+        registry.register(Box::new(musicbrainz_lookups.clone()))?;
+        registry.register(Box::new(db_query_duration.clone()))?;
+
+        Ok(Self {
+            registry,
+            scores_submitted,
+            dethrones,
+            songs_created,
+            musicbrainz_lookups,
+            db_query_duration,
+        })
+    }
+
+    /// Spawns a background task that pushes the current metric values to
+    /// `config.pushgateway_url` on a fixed interval.
+    ///
+    /// Push failures are logged and otherwise ignored so a flaky Pushgateway never affects
+    /// request handling. `prometheus::push_metrics` is a blocking network call, so it runs on a
+    /// `spawn_blocking` thread rather than tying up a tokio worker for the duration of the push.
+    pub fn spawn_pusher(&self, config: MetricsConfig) {
+        let registry = self.registry.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.push_interval);
+
+            loop {
+                interval.tick().await;
+
+                let metric_families = registry.gather();
+                let job_name = config.job_name.clone();
+                let pushgateway_url = config.pushgateway_url.clone();
+
+                let result = tokio::task::spawn_blocking(move || {
+                    prometheus::push_metrics(
+                        &job_name,
+                        prometheus::labels! {},
+                        &pushgateway_url,
+                        metric_families,
+                        None,
+                    )
+                })
+                .await;
+
+                match result {
+                    Ok(Ok(())) => info!("Pushed metrics to Pushgateway at {}", config.pushgateway_url),
+                    Ok(Err(e)) => error!("Failed to push metrics to Pushgateway: {e}"),
+                    Err(e) => error!("Metrics push task panicked: {e}"),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,224 @@
+//! Song lookup, fuzzy deduplication and creation.
+
+use diesel::{dsl::exists, prelude::*, sql_types::Text};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use tracing::{info, warn};
+
+use crate::{
+    musicbrainz::{MusicBrainzClient, RateLimited},
+    schema::songs::dsl::*,
+    util::{errors::RouteError, similarity::combined_similarity},
+};
+
+diesel::define_sql_function! {
+    /// `pg_trgm`'s trigram similarity, used to rank fuzzy-match candidates in the database
+    /// instead of loading every same-modifier row into Rust to compare.
+    fn similarity(a: Text, b: Text) -> diesel::sql_types::Double;
+}
+
+#[derive(Queryable, Identifiable, Debug, Clone)]
+pub struct Song {
+    pub id: i32,
+    pub title: String,
+    pub artist: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::songs)]
+pub struct NewSong<'a> {
+    title: &'a str,
+    artist: &'a str,
+    modifiers: Vec<String>,
+}
+
+impl<'a> NewSong<'a> {
+    pub fn new(title: &'a str, artist: &'a str, modifiers: Vec<String>) -> Self {
+        Self {
+            title,
+            artist,
+            modifiers,
+        }
+    }
+
+    /// Finds an existing song by exact title/artist/modifiers match, falls back to a fuzzy
+    /// trigram match against same-modifier candidates, and only creates a new row if neither
+    /// finds a hit.
+    ///
+    /// `fuzzy_threshold` is the minimum combined title+artist similarity (0.0-1.0) required for
+    /// the fuzzy fallback to short-circuit creation; see `AppState::song_match_threshold`.
+    ///
+    /// Returns the resolved song along with whether a new row had to be created, so callers can
+    /// track song-creation metrics without re-querying.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any of the underlying queries fail.
+    pub async fn find_or_create(
+        &self,
+        conn: &mut AsyncPgConnection,
+        fuzzy_threshold: f64,
+    ) -> Result<(Song, bool), RouteError> {
+        if let Some(exact) = songs
+            .filter(title.eq(self.title))
+            .filter(artist.eq(self.artist))
+            .filter(modifiers.is_not_distinct_from(&self.modifiers))
+            .first::<Song>(conn)
+            .await
+            .optional()?
+        {
+            return Ok((exact, false));
+        }
+
+        // Rank same-modifier rows by `pg_trgm` similarity in Postgres before pulling anything
+        // into Rust, so catalogs with more than a handful of songs per modifier bucket still
+        // find their closest match instead of only ever considering the most recently inserted
+        // ones (a `LIMIT` ordered by `id` would silently stop matching against older entries).
+        const MAX_FUZZY_CANDIDATES: i64 = 20;
+
+        let query_text = format!("{} {}", self.title, self.artist);
+
+        let candidates = songs
+            .filter(modifiers.is_not_distinct_from(&self.modifiers))
+            .order(similarity(title.concat(" ").concat(artist), query_text).desc())
+            .limit(MAX_FUZZY_CANDIDATES)
+            .load::<Song>(conn)
+            .await?;
+
+        let best_fuzzy_match = candidates
+            .into_iter()
+            .map(|candidate| {
+                let similarity =
+                    combined_similarity(self.title, self.artist, &candidate.title, &candidate.artist);
+                (similarity, candidate)
+            })
+            .filter(|(similarity, _)| *similarity >= fuzzy_threshold)
+            .max_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        if let Some((similarity, song)) = best_fuzzy_match {
+            info!(
+                "Fuzzy-matched \"{} - {}\" to existing song {} (\"{} - {}\") with similarity {:.2}, skipping creation",
+                self.artist, self.title, song.id, song.artist, song.title, similarity
+            );
+            return Ok((song, false));
+        }
+
+        let song = diesel::insert_into(songs)
+            .values(self)
+            .get_result::<Song>(conn)
+            .await?;
+
+        Ok((song, true))
+    }
+}
+
+impl Song {
+    /// Attaches `recording_mbid` to this song, looking up extra metadata through the shared
+    /// rate-limited/cached `MusicBrainzClient`.
+    ///
+    /// A rate-limited or failed lookup is logged and otherwise tolerated: metadata can always be
+    /// filled in by a later submission of the same song.
+    ///
+    /// # Errors
+    ///
+    /// This fails if persisting the metadata fails.
+    pub async fn add_metadata_mbid(
+        &self,
+        recording_mbid: &str,
+        release_mbid_override: Option<&str>,
+        musicbrainz: &MusicBrainzClient,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<(), RouteError> {
+        use crate::schema::extra_song_info::dsl::{
+            extra_song_info, mbid, release_mbid, song_id,
+        };
+
+        let metadata = match musicbrainz.lookup_by_mbid(recording_mbid).await {
+            Ok(metadata) => metadata,
+            Err(RateLimited) => {
+                warn!(
+                    "MusicBrainz rate limit exhausted, skipping metadata fetch for song {}",
+                    self.id
+                );
+                return Ok(());
+            }
+        };
+
+        let Some(metadata) = metadata else {
+            return Ok(());
+        };
+
+        let resolved_release_mbid = release_mbid_override.or(metadata.release_mbid.as_deref());
+
+        diesel::insert_into(extra_song_info)
+            .values((
+                song_id.eq(self.id),
+                mbid.eq(&metadata.mbid),
+                release_mbid.eq(resolved_release_mbid),
+            ))
+            .on_conflict(song_id)
+            .do_update()
+            .set((
+                mbid.eq(&metadata.mbid),
+                release_mbid.eq(resolved_release_mbid),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fetches MusicBrainz metadata for this song by artist/title, skipping songs that already
+    /// have extra metadata attached. `length_ms` only keys the lookup cache; see
+    /// `MusicBrainzClient::lookup_by_title_artist`.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the existence check or the metadata insert fails.
+    pub async fn auto_add_metadata(
+        &self,
+        length_ms: i32,
+        musicbrainz: &MusicBrainzClient,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<(), RouteError> {
+        use crate::schema::extra_song_info::dsl::{extra_song_info, mbid, release_mbid, song_id};
+
+        let has_metadata: bool = diesel::select(exists(
+            extra_song_info.filter(song_id.eq(self.id)),
+        ))
+        .get_result(conn)
+        .await?;
+
+        if has_metadata {
+            return Ok(());
+        }
+
+        let metadata = match musicbrainz
+            .lookup_by_title_artist(&self.artist, &self.title, length_ms)
+            .await
+        {
+            Ok(metadata) => metadata,
+            Err(RateLimited) => {
+                warn!(
+                    "MusicBrainz rate limit exhausted, skipping metadata fetch for song {}",
+                    self.id
+                );
+                return Ok(());
+            }
+        };
+
+        let Some(metadata) = metadata else {
+            return Ok(());
+        };
+
+        diesel::insert_into(extra_song_info)
+            .values((
+                song_id.eq(self.id),
+                mbid.eq(&metadata.mbid),
+                release_mbid.eq(metadata.release_mbid.as_deref()),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
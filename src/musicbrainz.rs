@@ -0,0 +1,307 @@
+//! Shared MusicBrainz access: a token-bucket rate limiter and a short-TTL response cache.
+//!
+//! MusicBrainz enforces roughly one request per second per client and will throttle or ban
+//! bursty callers, so every lookup made by `Song::add_metadata_mbid`/`Song::auto_add_metadata`
+//! funnels through here instead of calling out directly.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use tokio::sync::Semaphore;
+use tracing::error;
+
+const CACHE_TTL: Duration = Duration::from_secs(30);
+const MUSICBRAINZ_API_BASE: &str = "https://musicbrainz.org/ws/2";
+/// MusicBrainz's usage policy requires a descriptive User-Agent identifying the application and a
+/// contact point; requests without one are liable to be throttled or blocked outright.
+const MUSICBRAINZ_USER_AGENT: &str = concat!(
+    "wavebreaker-rs/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/kusabana/wavebreaker-rs )"
+);
+/// Bounds how long a single MusicBrainz request can hang. `fetch_song_id`/`send_ride` must defer
+/// gracefully rather than block on a stalled connection, and the token bucket only guards against
+/// *too many* requests, not a slow one.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Eq, PartialEq, Hash)]
+enum CacheKey {
+    Mbid(String),
+    TitleArtistLength(String, String, i32),
+}
+
+struct CacheEntry {
+    metadata: Option<RecordingMetadata>,
+    cached_at: Instant,
+}
+
+/// Metadata MusicBrainz returned for a recording lookup.
+#[derive(Debug, Clone)]
+pub struct RecordingMetadata {
+    pub mbid: String,
+    pub release_mbid: Option<String>,
+}
+
+/// Returned when the token bucket has no tokens available. Callers are expected to skip the
+/// lookup for this request and let a later submission of the same song retry it, rather than
+/// blocking the score insert on a free token.
+#[derive(Debug)]
+pub struct RateLimited;
+
+#[derive(Deserialize)]
+struct RecordingLookupResponse {
+    #[serde(default)]
+    recordings: Vec<RecordingEntry>,
+}
+
+#[derive(Deserialize)]
+struct RecordingEntry {
+    id: String,
+    #[serde(default)]
+    releases: Vec<ReleaseEntry>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseEntry {
+    id: String,
+}
+
+impl From<RecordingEntry> for RecordingMetadata {
+    fn from(entry: RecordingEntry) -> Self {
+        Self {
+            mbid: entry.id,
+            release_mbid: entry.releases.into_iter().next().map(|release| release.id),
+        }
+    }
+}
+
+pub struct MusicBrainzClient {
+    http: reqwest::Client,
+    bucket_capacity: usize,
+    bucket: Semaphore,
+    cache: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+impl MusicBrainzClient {
+    /// `refill_interval` controls how often a single token is returned to the bucket; MusicBrainz
+    /// asks for roughly one request per second, so this is normally `Duration::from_secs(1)`.
+    #[must_use]
+    pub fn new(bucket_capacity: usize, refill_interval: Duration) -> Arc<Self> {
+        let client = Arc::new(Self {
+            http: reqwest::Client::builder()
+                .user_agent(MUSICBRAINZ_USER_AGENT)
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("failed to build MusicBrainz HTTP client"),
+            bucket_capacity,
+            bucket: Semaphore::new(bucket_capacity),
+            cache: Mutex::new(HashMap::new()),
+        });
+
+        let refill_target = client.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(refill_interval);
+            loop {
+                interval.tick().await;
+                if refill_target.bucket.available_permits() < refill_target.bucket_capacity {
+                    refill_target.bucket.add_permits(1);
+                }
+                refill_target.sweep_expired();
+            }
+        });
+
+        client
+    }
+
+    /// Drops cache entries past their TTL. Piggybacks on the token-bucket refill interval rather
+    /// than running its own timer, since both only need to run a few times a second at most.
+    fn sweep_expired(&self) {
+        let mut cache = self.cache.lock().expect("cache mutex poisoned");
+        cache.retain(|_, entry| entry.cached_at.elapsed() < CACHE_TTL);
+    }
+
+    fn cached(&self, key: &CacheKey) -> Option<Option<RecordingMetadata>> {
+        let cache = self.cache.lock().expect("cache mutex poisoned");
+        cache.get(key).and_then(|entry| {
+            (entry.cached_at.elapsed() < CACHE_TTL).then(|| entry.metadata.clone())
+        })
+    }
+
+    fn store(&self, key: CacheKey, metadata: Option<RecordingMetadata>) {
+        let mut cache = self.cache.lock().expect("cache mutex poisoned");
+        cache.insert(
+            key,
+            CacheEntry {
+                metadata,
+                cached_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Looks up a recording by MBID, consulting the cache before making a network request and
+    /// deferring gracefully instead of blocking when the token bucket is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RateLimited` if no token is currently available.
+    pub async fn lookup_by_mbid(
+        &self,
+        recording_mbid: &str,
+    ) -> Result<Option<RecordingMetadata>, RateLimited> {
+        let key = CacheKey::Mbid(recording_mbid.to_owned());
+        if let Some(cached) = self.cached(&key) {
+            return Ok(cached);
+        }
+
+        let Ok(permit) = self.bucket.try_acquire() else {
+            return Err(RateLimited);
+        };
+        permit.forget();
+
+        let url = format!("{MUSICBRAINZ_API_BASE}/recording/{recording_mbid}?inc=releases&fmt=json");
+        let response = match self.http.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                error!("MusicBrainz request failed for recording {recording_mbid}: {e}");
+                // A transport failure doesn't mean the recording doesn't exist — don't cache it
+                // as a negative result, so the next submission of this song retries the lookup.
+                return Ok(None);
+            }
+        };
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            self.store(key, None);
+            return Ok(None);
+        }
+
+        let metadata = match response.json::<RecordingEntry>().await {
+            Ok(recording) => Some(RecordingMetadata::from(recording)),
+            Err(e) => {
+                error!("Failed to decode MusicBrainz response for recording {recording_mbid}: {e}");
+                return Ok(None);
+            }
+        };
+
+        self.store(key, metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Looks up a recording by artist/title, same caching and rate-limit semantics as
+    /// [`Self::lookup_by_mbid`]. `length_ms` is part of the cache key only; it is not sent to
+    /// MusicBrainz, so the search does not filter by duration and takes the first recording found.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RateLimited` if no token is currently available.
+    pub async fn lookup_by_title_artist(
+        &self,
+        artist: &str,
+        title: &str,
+        length_ms: i32,
+    ) -> Result<Option<RecordingMetadata>, RateLimited> {
+        let key = CacheKey::TitleArtistLength(artist.to_owned(), title.to_owned(), length_ms);
+        if let Some(cached) = self.cached(&key) {
+            return Ok(cached);
+        }
+
+        let Ok(permit) = self.bucket.try_acquire() else {
+            return Err(RateLimited);
+        };
+        permit.forget();
+
+        let query = format!("artist:{artist} AND recording:{title}");
+        let url = format!(
+            "{MUSICBRAINZ_API_BASE}/recording?query={}&fmt=json",
+            urlencoding::encode(&query)
+        );
+        let response = match self.http.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                error!("MusicBrainz search request failed for \"{artist} - {title}\": {e}");
+                // Same as above: a transport failure isn't a genuine "not found", so it must not
+                // be cached as one.
+                return Ok(None);
+            }
+        };
+
+        let body = match response.json::<RecordingLookupResponse>().await {
+            Ok(body) => body,
+            Err(e) => {
+                error!(
+                    "Failed to decode MusicBrainz search response for \"{artist} - {title}\": {e}"
+                );
+                return Ok(None);
+            }
+        };
+
+        let metadata = body.recordings.into_iter().next().map(Into::into);
+        self.store(key, metadata.clone());
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> MusicBrainzClient {
+        MusicBrainzClient {
+            http: reqwest::Client::new(),
+            bucket_capacity: 1,
+            bucket: Semaphore::new(1),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn backdated_entry(metadata: Option<RecordingMetadata>, age: Duration) -> CacheEntry {
+        CacheEntry {
+            metadata,
+            cached_at: Instant::now() - age,
+        }
+    }
+
+    #[test]
+    fn cache_hit_returns_stored_value() {
+        let client = client();
+        let key = CacheKey::Mbid("abc".to_owned());
+        client.store(key.clone(), Some(RecordingMetadata { mbid: "abc".to_owned(), release_mbid: None }));
+
+        let cached = client.cached(&key).expect("entry should be cached");
+        assert_eq!(cached.expect("entry should have metadata").mbid, "abc");
+    }
+
+    #[test]
+    fn cache_entry_past_ttl_is_not_returned() {
+        let client = client();
+        let key = CacheKey::Mbid("abc".to_owned());
+        client
+            .cache
+            .lock()
+            .unwrap()
+            .insert(key.clone(), backdated_entry(None, CACHE_TTL + Duration::from_secs(1)));
+
+        assert!(client.cached(&key).is_none());
+    }
+
+    #[test]
+    fn sweep_expired_drops_only_stale_entries() {
+        let client = client();
+        let fresh_key = CacheKey::Mbid("fresh".to_owned());
+        let stale_key = CacheKey::Mbid("stale".to_owned());
+        {
+            let mut cache = client.cache.lock().unwrap();
+            cache.insert(fresh_key.clone(), backdated_entry(None, Duration::from_secs(1)));
+            cache.insert(stale_key.clone(), backdated_entry(None, CACHE_TTL + Duration::from_secs(1)));
+        }
+
+        client.sweep_expired();
+
+        let cache = client.cache.lock().unwrap();
+        assert!(cache.contains_key(&fresh_key));
+        assert!(!cache.contains_key(&stale_key));
+    }
+}
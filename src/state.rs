@@ -0,0 +1,26 @@
+//! Shared application state handed to every Axum handler.
+
+use diesel_async::{pooled_connection::bb8::Pool, AsyncPgConnection};
+use redis::aio::ConnectionManager;
+use tokio::sync::broadcast;
+
+use crate::{game::notifications::DethroneEvent, musicbrainz::MusicBrainzClient, util::steam::SteamApi};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Pool<AsyncPgConnection>,
+    pub redis: ConnectionManager,
+    pub steam_api: SteamApi,
+    /// Minimum combined title+artist trigram similarity (0.0-1.0) for `fetch_song_id` to treat a
+    /// lookup as a match against an existing song instead of creating a new one. Defaults to 0.6;
+    /// tune via the `SONG_MATCH_THRESHOLD` environment variable.
+    pub song_match_threshold: f64,
+    /// Publishes dethrone notifications for `game::notifications::spawn_dethrone_consumer`'s
+    /// consumer task to pick up and hand to the configured dispatcher(s). Sending is a no-op
+    /// (besides the `Err` it returns, which callers ignore) if no consumer was spawned.
+    pub dethrone_events: broadcast::Sender<DethroneEvent>,
+    /// Shared rate-limited, cached MusicBrainz client used by all metadata lookups.
+    pub musicbrainz: std::sync::Arc<MusicBrainzClient>,
+    #[cfg(feature = "metrics")]
+    pub metrics: crate::metrics::Metrics,
+}
@@ -0,0 +1,138 @@
+//! Lightweight Redis-backed live server stats, enabled via the `stats` cargo feature.
+//!
+//! Reuses the Redis connection already threaded into `AppState` for score persistence so a
+//! frontend or Discord bot can show "most played songs today" / "players online" without
+//! hammering Postgres.
+
+use axum::{extract::State, http::StatusCode, Json};
+use redis::AsyncCommands;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::{
+    util::errors::{IntoRouteError, RouteError},
+    AppState,
+};
+
+const TOTAL_SCORES_KEY: &str = "stats:scores_submitted_total";
+const ACTIVE_PLAYERS_KEY: &str = "stats:active_players";
+const ACTIVE_PLAYERS_WINDOW_SECS: i64 = 15 * 60;
+/// Per-day "plays today" keys are kept around a couple of days past midnight so a request
+/// crossing the day boundary still sees them, then left to expire instead of accumulating one
+/// permanent sorted set per calendar day forever.
+const PLAYS_TODAY_TTL_SECS: i64 = 3 * 24 * 60 * 60;
+
+fn plays_today_key() -> String {
+    let today = OffsetDateTime::now_utc().date();
+    format!("stats:plays:{today}")
+}
+
+/// Records a score submission: bumps the running total and the per-song "plays today" sorted set.
+pub async fn record_score_submitted(
+    redis: &mut redis::aio::ConnectionManager,
+    song_id: i32,
+) -> redis::RedisResult<()> {
+    let _: () = redis.incr(TOTAL_SCORES_KEY, 1).await?;
+
+    let plays_key = plays_today_key();
+    let _: () = redis.zincr(&plays_key, song_id, 1).await?;
+    redis.expire(&plays_key, PLAYS_TODAY_TTL_SECS).await
+}
+
+/// Marks a player active in the rolling window. Called on every successful `ticket_auth`.
+pub async fn record_active_player(
+    redis: &mut redis::aio::ConnectionManager,
+    steam_id: u64,
+) -> redis::RedisResult<()> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let _: () = redis.zadd(ACTIVE_PLAYERS_KEY, steam_id, now).await?;
+    redis
+        .zrembyscore(ACTIVE_PLAYERS_KEY, 0, now - ACTIVE_PLAYERS_WINDOW_SECS)
+        .await
+}
+
+#[derive(Serialize)]
+pub struct LiveStats {
+    players_online: u64,
+    scores_submitted_total: u64,
+    most_played_today: Vec<SongPlays>,
+}
+
+#[derive(Serialize)]
+pub struct SongPlays {
+    song_id: i32,
+    plays: u64,
+}
+
+/// Returns a snapshot of Redis-backed live stats: players online in the last 15 minutes, total
+/// scores submitted, and the most-played songs today.
+///
+/// # Errors
+///
+/// This fails if any of the underlying Redis commands fail.
+pub async fn get_live_stats(State(state): State<AppState>) -> Result<Json<LiveStats>, RouteError> {
+    let mut redis = state.redis.clone();
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+
+    redis
+        .zrembyscore::<_, _, _, ()>(ACTIVE_PLAYERS_KEY, 0, now - ACTIVE_PLAYERS_WINDOW_SECS)
+        .await
+        .http_error(
+            "Failed to prune stale players",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )?;
+
+    let players_online: u64 = redis
+        .zcard(ACTIVE_PLAYERS_KEY)
+        .await
+        .http_error("Failed to read players online", StatusCode::INTERNAL_SERVER_ERROR)?;
+    let scores_submitted_total: u64 = redis
+        .get::<_, Option<u64>>(TOTAL_SCORES_KEY)
+        .await
+        .http_error("Failed to read total scores", StatusCode::INTERNAL_SERVER_ERROR)?
+        .unwrap_or(0);
+
+    let most_played_today: Vec<(i32, u64)> = redis
+        .zrevrange_withscores(plays_today_key(), 0, 9)
+        .await
+        .http_error(
+            "Failed to read most played songs",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )?;
+
+    Ok(Json(LiveStats {
+        players_online,
+        scores_submitted_total,
+        most_played_today: most_played_today
+            .into_iter()
+            .map(|(song_id, plays)| SongPlays { song_id, plays })
+            .collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_today_key_is_prefixed_and_dated() {
+        let key = plays_today_key();
+        let today = OffsetDateTime::now_utc().date();
+
+        assert_eq!(key, format!("stats:plays:{today}"));
+        assert!(key.starts_with("stats:plays:"));
+    }
+
+    #[test]
+    fn plays_today_ttl_outlives_a_calendar_day() {
+        // The TTL must comfortably exceed one day, or a day's key last written just after
+        // midnight could expire before that day is over and `get_live_stats` stops reading it.
+        assert!(PLAYS_TODAY_TTL_SECS > 24 * 60 * 60);
+    }
+
+    #[test]
+    fn active_players_window_is_positive_and_reasonable() {
+        assert!(ACTIVE_PLAYERS_WINDOW_SECS > 0);
+        assert!(ACTIVE_PLAYERS_WINDOW_SECS <= 60 * 60);
+    }
+}
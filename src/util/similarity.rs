@@ -0,0 +1,81 @@
+//! Fuzzy string matching helpers used to deduplicate near-identical song titles/artists.
+
+use std::collections::HashSet;
+
+/// Splits a normalized string into the set of overlapping 3-character substrings ("trigrams").
+///
+/// The input is lowercased, punctuation is stripped, and the result is padded with two leading
+/// spaces and one trailing space so short words still contribute boundary trigrams.
+fn trigrams(input: &str) -> HashSet<String> {
+    let normalized: String = input
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+
+    let padded: Vec<char> = format!("  {normalized} ").chars().collect();
+
+    if padded.len() < 3 {
+        return HashSet::from([padded.into_iter().collect()]);
+    }
+
+    padded
+        .windows(3)
+        .map(|w| w.iter().collect::<String>())
+        .collect()
+}
+
+/// Jaccard similarity (`|intersection| / |union|`) between the trigram sets of two strings.
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let set_a = trigrams(a);
+    let set_b = trigrams(b);
+
+    let union = set_a.union(&set_b).count();
+    if union == 0 {
+        return 1.0;
+    }
+
+    set_a.intersection(&set_b).count() as f64 / union as f64
+}
+
+/// Combined title+artist similarity, averaging both fields' trigram similarity.
+pub fn combined_similarity(a_title: &str, a_artist: &str, b_title: &str, b_artist: &str) -> f64 {
+    (trigram_similarity(a_title, b_title) + trigram_similarity(a_artist, b_artist)) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_strings_are_fully_similar() {
+        assert!((trigram_similarity("deadmau5", "deadmau5") - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn casing_alone_does_not_reduce_similarity() {
+        assert!((trigram_similarity("Deadmau5", "deadmau5") - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn punctuation_variants_are_highly_similar() {
+        let similarity = trigram_similarity("Strobe (Radio Edit)", "Strobe - Radio Edit");
+        assert!(similarity > 0.8, "expected high similarity, got {similarity}");
+    }
+
+    #[test]
+    fn unrelated_strings_are_dissimilar() {
+        let similarity = trigram_similarity("Strobe", "Ghosts 'n' Stuff");
+        assert!(similarity < 0.2, "expected low similarity, got {similarity}");
+    }
+
+    #[test]
+    fn combined_similarity_averages_title_and_artist() {
+        let similarity = combined_similarity("Strobe", "deadmau5", "Strobe", "Deadmau5");
+        assert!((similarity - 1.0).abs() < f64::EPSILON);
+
+        // Identical titles (1.0) and artists sharing no trigram (0.0) average to 0.5.
+        let half = combined_similarity("Strobe", "deadmau5", "Strobe", "Ghosts");
+        assert!((half - 0.5).abs() < f64::EPSILON, "expected 0.5, got {half}");
+    }
+}